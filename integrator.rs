@@ -0,0 +1,90 @@
+use nalgebra as na;
+
+use crate::Planet;
+
+/// A pluggable force/integration scheme for advancing an n-body system.
+///
+/// Keeping this decoupled from `SolarSystem`/`Model` lets the core engine run
+/// headless (tests, benchmarks) without depending on the nannou window, and
+/// lets callers swap integration schemes without touching draw code.
+pub trait Integrator {
+    /// Computes each planet's acceleration from mutual gravity. `eps` softens
+    /// close encounters so the force doesn't blow up when bodies nearly
+    /// overlap.
+    fn compute_accelerations(&self, planets: &[Planet], g: f64, eps: f64) -> Vec<na::Vector3<f64>>;
+
+    /// Advances all planets by one timestep `dt`.
+    fn step(&mut self, planets: &mut [Planet], g: f64, eps: f64, dt: f64);
+}
+
+/// Computes accelerations in a single pass over unordered pairs, applying
+/// Newton's third law (`forces[i] += f; forces[j] -= f`) instead of visiting
+/// each ordered pair and computing the interaction twice.
+fn gravitational_accelerations(planets: &[Planet], g: f64, eps: f64) -> Vec<na::Vector3<f64>> {
+    let mut forces = vec![na::Vector3::zeros(); planets.len()];
+    let eps2 = eps * eps;
+
+    for i in 0..planets.len() {
+        for j in (i + 1)..planets.len() {
+            let d = planets[j].position - planets[i].position;
+            let d2 = d.dot(&d) + eps2;
+            let inv = g / (d2 * d2.sqrt());
+            let f = d * (planets[i].mass * planets[j].mass * inv);
+            forces[i] += f;
+            forces[j] -= f;
+        }
+    }
+
+    forces
+        .iter()
+        .zip(planets)
+        .map(|(force, planet)| force / planet.mass)
+        .collect()
+}
+
+/// Semi-implicit Euler: `velocity += a*dt; position += v*dt`, with the
+/// position advanced by the already-updated velocity. Kept around for
+/// comparison against `VerletIntegrator`; being only first-order, its energy
+/// error is much larger than Verlet's at the same `dt`.
+#[derive(Default)]
+pub struct EulerIntegrator;
+
+impl Integrator for EulerIntegrator {
+    fn compute_accelerations(&self, planets: &[Planet], g: f64, eps: f64) -> Vec<na::Vector3<f64>> {
+        gravitational_accelerations(planets, g, eps)
+    }
+
+    fn step(&mut self, planets: &mut [Planet], g: f64, eps: f64, dt: f64) {
+        let accelerations = self.compute_accelerations(planets, g, eps);
+
+        for (planet, &acceleration) in planets.iter_mut().zip(&accelerations) {
+            planet.velocity += acceleration * dt;
+            planet.position += planet.velocity * dt;
+        }
+    }
+}
+
+/// Velocity-Verlet (leapfrog): reuses each planet's cached acceleration from
+/// the previous step, so forces are evaluated once per step just like Euler,
+/// but energy is conserved far better for the large `dt` this sim uses.
+#[derive(Default)]
+pub struct VerletIntegrator;
+
+impl Integrator for VerletIntegrator {
+    fn compute_accelerations(&self, planets: &[Planet], g: f64, eps: f64) -> Vec<na::Vector3<f64>> {
+        gravitational_accelerations(planets, g, eps)
+    }
+
+    fn step(&mut self, planets: &mut [Planet], g: f64, eps: f64, dt: f64) {
+        for planet in planets.iter_mut() {
+            planet.position += planet.velocity * dt + 0.5 * planet.acceleration * dt * dt;
+        }
+
+        let new_accelerations = self.compute_accelerations(planets, g, eps);
+
+        for (planet, &new_acceleration) in planets.iter_mut().zip(&new_accelerations) {
+            planet.velocity += 0.5 * (planet.acceleration + new_acceleration) * dt;
+            planet.acceleration = new_acceleration;
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use nalgebra as na;
 use nannou::prelude::*;
 
+mod integrator;
+
+use integrator::{EulerIntegrator, Integrator, VerletIntegrator};
+
 #[derive(Debug)]
 struct Planet {
     name: String,
     mass: f64,
     position: na::Vector3<f64>,
     velocity: na::Vector3<f64>,
+    // Acceleration from the previous step, cached so the velocity-Verlet
+    // integrator can reuse it instead of recomputing forces twice per step.
+    acceleration: na::Vector3<f64>,
     color: Rgb<u8>,
 }
 
@@ -23,6 +30,7 @@ impl Planet {
             mass,
             position,
             velocity,
+            acceleration: na::Vector3::zeros(),
             color,
         }
     }
@@ -31,13 +39,23 @@ impl Planet {
 struct SolarSystem {
     planets: Vec<Planet>,
     gravitational_constant: f64,
+    // Softening length for close encounters: `d2 + softening^2` in the
+    // denominator keeps the force finite when two bodies nearly overlap.
+    softening: f64,
+    integrator: Box<dyn Integrator>,
 }
 
 impl SolarSystem {
     fn new() -> Self {
+        Self::with_integrator(Box::new(VerletIntegrator))
+    }
+
+    fn with_integrator(integrator: Box<dyn Integrator>) -> Self {
         Self {
             planets: Vec::new(),
             gravitational_constant: 6.67430e-11, // m^3 kg^-1 s^-2
+            softening: 1.0e6,                    // meters
+            integrator,
         }
     }
 
@@ -45,33 +63,88 @@ impl SolarSystem {
         self.planets.push(planet);
     }
 
-    fn compute_gravitational_forces(&self) -> Vec<na::Vector3<f64>> {
-        let mut forces = vec![na::Vector3::zeros(); self.planets.len()];
+    /// Seeds each planet's cached acceleration from the actual initial
+    /// gravitational field. Velocity-Verlet needs `a(t0)` for its first
+    /// `position` update; leaving it at zero would silently drop the initial
+    /// force's contribution for one full step.
+    fn init_accelerations(&mut self) {
+        let accelerations = self.integrator.compute_accelerations(
+            &self.planets,
+            self.gravitational_constant,
+            self.softening,
+        );
+
+        for (planet, &acceleration) in self.planets.iter_mut().zip(&accelerations) {
+            planet.acceleration = acceleration;
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        self.integrator.step(
+            &mut self.planets,
+            self.gravitational_constant,
+            self.softening,
+            dt,
+        );
+    }
+
+    /// Swaps the integration scheme at runtime, re-seeding the cached
+    /// acceleration so whichever scheme is switched to next (Verlet in
+    /// particular) starts from the true current field instead of a stale one.
+    fn set_integrator(&mut self, integrator: Box<dyn Integrator>) {
+        self.integrator = integrator;
+        self.init_accelerations();
+    }
+
+    /// Kinetic plus potential energy of the system, used to validate that the
+    /// integrator conserves energy over long runs.
+    fn total_energy(&self) -> f64 {
+        let kinetic: f64 = self
+            .planets
+            .iter()
+            .map(|planet| 0.5 * planet.mass * planet.velocity.magnitude_squared())
+            .sum();
 
+        let eps2 = self.softening * self.softening;
+        let mut potential = 0.0;
         for i in 0..self.planets.len() {
-            for j in 0..self.planets.len() {
-                if i != j {
-                    let direction = self.planets[j].position - self.planets[i].position;
-                    let distance = direction.magnitude();
-                    let force_magnitude =
-                        self.gravitational_constant * self.planets[i].mass * self.planets[j].mass
-                            / distance.powi(2);
-                    let force = direction.normalize() * force_magnitude;
-                    forces[i] += force;
-                }
+            for j in (i + 1)..self.planets.len() {
+                let d = self.planets[j].position - self.planets[i].position;
+                let distance = (d.dot(&d) + eps2).sqrt();
+                potential -= self.gravitational_constant * self.planets[i].mass
+                    * self.planets[j].mass
+                    / distance;
             }
         }
 
-        forces
+        kinetic + potential
     }
 
-    fn update_positions(&mut self, dt: f64) {
-        let forces = self.compute_gravitational_forces();
+    /// Total linear momentum Σ m_i·v_i.
+    fn total_momentum(&self) -> na::Vector3<f64> {
+        self.planets
+            .iter()
+            .map(|planet| planet.mass * planet.velocity)
+            .sum()
+    }
+
+    /// Total angular momentum Σ m_i·(r_i × v_i) about the origin.
+    fn angular_momentum(&self) -> na::Vector3<f64> {
+        self.planets
+            .iter()
+            .map(|planet| planet.mass * planet.position.cross(&planet.velocity))
+            .sum()
+    }
 
-        for (i, planet) in self.planets.iter_mut().enumerate() {
-            let acceleration = forces[i] / planet.mass;
-            planet.velocity += acceleration * dt;
-            planet.position += planet.velocity * dt;
+    /// Cancels the system's net linear momentum by setting the Sun's (the
+    /// first planet's) velocity to `-p / m_sun`, keeping the barycenter fixed
+    /// where it starts so the view doesn't slowly drift off-screen over long
+    /// runs. The Sun's velocity is replaced, not adjusted, so the net momentum
+    /// comes out zero only when the Sun is at rest before the call.
+    fn offset_momentum(&mut self) {
+        let momentum = self.total_momentum();
+        if let Some(sun) = self.planets.first_mut() {
+            sun.velocity = -momentum / sun.mass;
         }
     }
 }
@@ -84,6 +157,7 @@ struct Model {
     solar_system: SolarSystem,
     speed_multiplier: f64,
     scale_factor: f64,
+    initial_energy: f64,
 }
 
 fn model(app: &App) -> Model {
@@ -178,16 +252,22 @@ fn model(app: &App) -> Model {
     );
     solar_system.add_planet(neptune);
 
+    solar_system.init_accelerations();
+    solar_system.offset_momentum();
+
+    let initial_energy = solar_system.total_energy();
+
     Model {
         solar_system,
         speed_multiplier: 1.0,
         scale_factor: 1.0e9, // 初期スケールファクター
+        initial_energy,
     }
 }
 
 fn update(_app: &App, model: &mut Model, _update: Update) {
     let dt = 60.0 * model.speed_multiplier; // 秒
-    model.solar_system.update_positions(dt);
+    model.solar_system.step(dt);
 }
 
 fn view(app: &App, model: &Model, frame: Frame) {
@@ -201,6 +281,18 @@ fn view(app: &App, model: &Model, frame: Frame) {
         draw.ellipse().color(planet.color).x_y(x, y).radius(radius);
     }
 
+    let energy = model.solar_system.total_energy();
+    let drift = (energy - model.initial_energy) / model.initial_energy.abs();
+    let angular_momentum = model.solar_system.angular_momentum().magnitude();
+    draw.text(&format!(
+        "energy: {:.6e}  drift: {:+.4}%  |L|: {:.6e}",
+        energy,
+        drift * 100.0,
+        angular_momentum
+    ))
+    .x_y(0.0, app.window_rect().top() - 20.0)
+    .color(WHITE);
+
     draw.to_frame(app, &frame).unwrap();
 }
 
@@ -222,6 +314,14 @@ fn key_pressed(_app: &App, model: &mut Model, key: Key) {
             model.scale_factor /= 1.1;
             println!("Scale factor decreased to: {}", model.scale_factor);
         }
+        Key::E => {
+            model.solar_system.set_integrator(Box::new(EulerIntegrator));
+            println!("Switched to Euler integration");
+        }
+        Key::V => {
+            model.solar_system.set_integrator(Box::new(VerletIntegrator));
+            println!("Switched to Verlet integration");
+        }
         _ => {}
     }
 }